@@ -0,0 +1,205 @@
+//! A thread-safe, reference-counted pointer that accepts DST: `Arc<str>`, `Arc<[T]>`, `Arc<Fn>`,
+//! etc
+
+use core::nonzero::NonZero;
+use std::boxed;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering, fence};
+
+/// A thread-safe, reference-counted pointer type over an immutable value.
+///
+/// This is the `Send + Sync` counterpart of `Rc`: it has the same split layout (a small count
+/// allocation plus a `*mut T` data pointer) but the count is an `AtomicUsize` instead of a
+/// `Cell<usize>`, so `clone` and `drop` can race across threads safely. `clone` performs a
+/// relaxed `fetch_add`; `drop` performs a `fetch_sub` with release ordering, followed by an
+/// acquire fence before the final deallocation, so the data is only freed once the last
+/// decrementer has observed every prior mutation.
+///
+/// # Examples
+///
+/// Unlike `Rc`, an `Arc` can be moved into another thread.
+///
+/// ```
+/// # extern crate rc;
+/// # use rc::Arc;
+/// # use std::thread;
+/// # fn main() {
+/// let arc = Arc::new(42);
+/// let moved = arc.clone();  // increases refcount to 2
+///
+/// let handle = thread::spawn(move || {
+///     assert_eq!(*moved, 42);
+///     // `moved` dropped here, refcount decreases to 1
+/// });
+///
+/// handle.join().unwrap();
+/// assert_eq!(*arc, 42);
+/// // `arc` dropped here, refcount reaches zero, the value is deallocated
+/// # }
+/// ```
+///
+/// # Layout
+///
+/// A single layer of indirection, same as `Rc`.
+///
+/// ``` text
+///    Stack             |   Heap
+///                       |
+///    Arc<str>          |
+/// +-------------------+ |
+/// | *mut str          |-|-> "Hello, world!"
+/// | *mut AtomicUsize  |-|-> 3
+/// +-------------------+ |
+/// ```
+///
+/// ^ NOTE: `NonZero` wrapper omitted for brevity. String and reference count are not
+/// (necessarily) stored in contiguous memory.
+///
+/// # Size
+///
+/// For sized types: 2 words, for DST: 3 words, same as `Rc`.
+///
+/// ```
+/// # extern crate rc;
+/// # use std::mem;
+/// # use rc::Arc;
+/// # fn main() {
+/// assert_eq!(mem::size_of::<Arc<()>>(),    2 * mem::size_of::<usize>());
+/// assert_eq!(mem::size_of::<Arc<[i32]>>(), 3 * mem::size_of::<usize>());
+/// assert_eq!(mem::size_of::<Arc<str>>(),   3 * mem::size_of::<usize>());
+/// # }
+/// ```
+#[unsafe_no_drop_flag]
+pub struct Arc<T: ?Sized> {
+    /// The number of references
+    count: NonZero<*mut AtomicUsize>,
+    /// A pointer to the heap allocated data
+    data: NonZero<*mut T>,
+}
+
+impl<T> Arc<T> {
+    /// Creates a new `Arc` pointer.
+    ///
+    /// NOTE: `value` will be allocated in the heap. If you have a heap allocated value like `Box`,
+    /// `String` or `Vec`, use the `Arc::from()` method instead.
+    pub fn new(value: T) -> Arc<T> {
+        Arc::from(Box::new(value))
+    }
+}
+
+impl<T: ?Sized> Arc<T> {
+    /// Returns the number of references to this value.
+    pub fn count(&self) -> usize {
+        unsafe {
+            (**self.count).load(Ordering::SeqCst)
+        }
+    }
+}
+
+impl<T: ?Sized> Clone for Arc<T> {
+    fn clone(&self) -> Arc<T> {
+        unsafe {
+            (**self.count).fetch_add(1, Ordering::Relaxed);
+        }
+
+        Arc {
+            count: self.count,
+            data: self.data,
+        }
+    }
+}
+
+impl<T: ?Sized> Eq for Arc<T> where T: Eq {}
+
+impl<'a, T> From<&'a [T]> for Arc<[T]> where T: Clone {
+    /// NOTE: This requires allocating the `slice` first (`Vec::to_vec`).
+    fn from(slice: &[T]) -> Arc<[T]> {
+        Arc::from(slice.to_vec())
+    }
+}
+
+impl<'a> From<&'a str> for Arc<str> {
+    /// NOTE: This requires allocating the `string` first (`String::from_str`).
+    fn from(string: &str) -> Arc<str> {
+        Arc::from(String::from_str(string))
+    }
+}
+
+impl<T: ?Sized> From<Box<T>> for Arc<T> {
+    /// NOTE: this involves a single, small heap allocation for the reference count. `boxed_value`
+    /// will *not* be reallocated.
+    fn from(boxed_value: Box<T>) -> Arc<T> {
+        unsafe {
+            Arc {
+                count: NonZero::new(boxed::into_raw(Box::new(AtomicUsize::new(1)))),
+                data: NonZero::new(boxed::into_raw(boxed_value)),
+            }
+        }
+    }
+}
+
+// TODO(rust-lang/rust#18283) use `Arc::from(string.into_boxed_str())` instead of `transmute`
+impl From<String> for Arc<str> {
+    /// NOTE: This calls `shrink_to_fit` on `string` (on the underlying `Vec<u8>`), which may incur
+    /// in a reallocation.
+    fn from(string: String) -> Arc<str> {
+        // Create a `Arc<[u8]>` first, and then transmute that into a `Arc<str>`
+        unsafe {
+            mem::transmute(Arc::from(string.into_bytes()))
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for Arc<[T]> {
+    /// NOTE: This calls `shrink_to_fit` on `vec`, which may incur in a reallocation.
+    fn from(vec: Vec<T>) -> Arc<[T]> {
+        Arc::from(vec.into_boxed_slice())
+    }
+}
+
+impl<T: ?Sized> Deref for Arc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe {
+            mem::transmute(*self.data)
+        }
+    }
+}
+
+impl<T: ?Sized> Drop for Arc<T> {
+    fn drop(&mut self) {
+        let ptr = *self.count;
+
+        if !ptr.is_null() && ptr as usize != mem::POST_DROP_USIZE {
+            unsafe {
+                if (**self.count).fetch_sub(1, Ordering::Release) != 1 {
+                    return;
+                }
+
+                // Synchronize with every other decrement before the data is torn down.
+                fence(Ordering::Acquire);
+
+                drop(Box::from_raw(*self.data));
+                drop(Box::from_raw(*self.count));
+            }
+        }
+    }
+}
+
+impl<T: ?Sized> Hash for Arc<T> where T: Hash {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        Hash::hash(&**self, state)
+    }
+}
+
+impl<T: ?Sized> PartialEq for Arc<T> where T: PartialEq {
+    fn eq(&self, rhs: &Arc<T>) -> bool {
+        PartialEq::eq(&**self, &**rhs)
+    }
+}
+
+unsafe impl<T: ?Sized> Send for Arc<T> where T: Send + Sync {}
+unsafe impl<T: ?Sized> Sync for Arc<T> where T: Send + Sync {}
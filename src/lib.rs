@@ -19,13 +19,20 @@
 extern crate alloc;
 extern crate core;
 
+mod arc;
+
+pub use arc::Arc;
+
+use alloc::heap;
 use core::nonzero::NonZero;
 use std::borrow::Borrow;
 use std::boxed;
 use std::cell::Cell;
 use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
 use std::mem;
 use std::ops::Deref;
+use std::ptr;
 
 /// A reference-counted pointer type over an immutable value.
 ///
@@ -83,12 +90,66 @@ use std::ops::Deref;
 /// ```
 #[unsafe_no_drop_flag]
 pub struct Rc<T: ?Sized> {
-    /// The number of references
-    count: NonZero<*mut Cell<usize>>,
+    /// The strong/weak reference counts
+    count: NonZero<*mut Counts>,
     /// A pointer to the heap allocated data
     data: NonZero<*mut T>,
 }
 
+/// The pair of strong/weak counters shared by a `Rc`/`Weak` family.
+///
+/// `strong` and `weak` are tracked independently: `downgrade`, `Weak::clone` and `Weak::drop`
+/// only ever touch `weak`, never `strong`. The count block itself is only freed once both
+/// reach zero, which `Rc::drop` and `Weak::drop` each check independently (whichever of the two
+/// drops last is the one that frees it) — this is what lets the block outlive the data for as
+/// long as any `Weak` is still alive.
+struct Counts {
+    strong: Cell<usize>,
+    weak: Cell<usize>,
+}
+
+/// An allocation failure.
+///
+/// Returned by the `try_*` constructors instead of aborting the process, so that callers running
+/// under memory pressure (kernel-style or embedded environments) can recover instead of crashing.
+#[derive(Debug)]
+pub struct AllocError;
+
+/// Allocates space for a `U` on the heap and moves `value` into it, without aborting on failure.
+///
+/// NOTE: mirrors `Box`'s lang-item path by special-casing zero-sized `U`: the allocator is never
+/// invoked (and thus never consulted) for a zero-size request, so a zero-sized `value` can't
+/// spuriously turn into `Err(AllocError)`.
+fn try_alloc<U>(value: U) -> Result<*mut U, AllocError> {
+    unsafe {
+        let size = mem::size_of::<U>();
+
+        let ptr = if size == 0 {
+            mem::align_of::<U>() as *mut U
+        } else {
+            heap::allocate(size, mem::align_of::<U>()) as *mut U
+        };
+
+        if ptr.is_null() {
+            Err(AllocError)
+        } else {
+            ptr::write(ptr, value);
+            Ok(ptr)
+        }
+    }
+}
+
+/// Frees a heap allocation made by `try_alloc`, without running `U`'s destructor.
+fn dealloc<U>(ptr: *mut U) {
+    unsafe {
+        let size = mem::size_of::<U>();
+
+        if size != 0 {
+            heap::deallocate(ptr as *mut u8, size, mem::align_of::<U>());
+        }
+    }
+}
+
 impl<T> Rc<T> {
     /// Creates a new `Rc` pointer.
     ///
@@ -97,29 +158,245 @@ impl<T> Rc<T> {
     pub fn new(value: T) -> Rc<T> {
         Rc::from(Box::new(value))
     }
+
+    /// Attempts to create a new `Rc` pointer, returning `Err` instead of aborting the process if
+    /// either allocation (the count block or `value` itself) fails.
+    pub fn try_new(value: T) -> Result<Rc<T>, AllocError> {
+        unsafe {
+            let count = try!(try_alloc(Counts { strong: Cell::new(1), weak: Cell::new(0) }));
+
+            match try_alloc(value) {
+                Ok(data) => Ok(Rc {
+                    count: NonZero::new(count),
+                    data: NonZero::new(data),
+                }),
+                Err(e) => {
+                    dealloc(count);
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    /// Attempts to reclaim the owned value, returning `Ok(value)` if this is the only (strong)
+    /// reference to it, or `Err(self)` otherwise, leaving the `Rc` intact.
+    pub fn try_unwrap(self) -> Result<T, Rc<T>> {
+        if self.count() == 1 {
+            unsafe {
+                let value = ptr::read(*self.data);
+                let data = *self.data;
+                let count = *self.count;
+
+                // Suppress the normal `Drop` impl: `value` has already been moved out, and the
+                // heap blocks are deallocated by hand below (the count block only if no `Weak`
+                // remains).
+                mem::forget(self);
+
+                dealloc(data);
+
+                (*count).strong.set(0);
+
+                if (*count).weak.get() == 0 {
+                    dealloc(count);
+                }
+
+                Ok(value)
+            }
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Reclaims the owned value if this is the only (strong) reference to it, or `None`
+    /// otherwise. A convenience wrapper around `try_unwrap`.
+    pub fn into_inner(self) -> Option<T> {
+        self.try_unwrap().ok()
+    }
 }
 
 impl<T: ?Sized> Rc<T> {
-    /// Returns the number of references to this value.
+    /// Returns the number of strong references to this value.
     pub fn count(&self) -> usize {
         unsafe {
-            (**self.count).get()
+            (**self.count).strong.get()
+        }
+    }
+
+    /// Returns the number of `Weak` references to this value.
+    pub fn weak_count(&self) -> usize {
+        unsafe {
+            (**self.count).weak.get()
+        }
+    }
+
+    /// Creates a new `Weak` pointer to this value.
+    pub fn downgrade(&self) -> Weak<T> {
+        self.inc_weak();
+
+        Weak {
+            count: self.count,
+            data: self.data,
+        }
+    }
+
+    /// Attempts to create a new `Rc` from an already heap-allocated `boxed_value`, returning
+    /// `Err` instead of aborting if the (small) count allocation fails.
+    ///
+    /// NOTE: this involves a single, small heap allocation for the reference count.
+    /// `boxed_value` will *not* be reallocated.
+    pub fn try_from_box(boxed_value: Box<T>) -> Result<Rc<T>, AllocError> {
+        let count = try!(try_alloc(Counts { strong: Cell::new(1), weak: Cell::new(0) }));
+
+        unsafe {
+            Ok(Rc {
+                count: NonZero::new(count),
+                data: NonZero::new(boxed::into_raw(boxed_value)),
+            })
+        }
+    }
+
+    /// Returns a unique mutable reference to the owned value, if this is the only (strong)
+    /// reference to it and no `Weak` reference exists, or `None` otherwise.
+    ///
+    /// A live `Weak` must also rule this out: `Weak::upgrade` can turn into a second `Rc` (and
+    /// thus a `&T`) at any time, so a lone strong reference is not enough to guarantee
+    /// uniqueness.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if self.count() == 1 && self.weak_count() == 0 {
+            unsafe {
+                Some(mem::transmute(*self.data))
+            }
+        } else {
+            None
         }
     }
 
     fn dec_count(&self) {
         unsafe {
-            (**self.count).set(self.count() - 1)
+            (**self.count).strong.set(self.count() - 1)
         }
     }
 
     fn inc_count(&self) {
         unsafe {
-            (**self.count).set(self.count() + 1)
+            (**self.count).strong.set(self.count() + 1)
+        }
+    }
+
+    fn dec_weak(&self) {
+        unsafe {
+            (**self.count).weak.set(self.weak_count() - 1)
         }
     }
+
+    fn inc_weak(&self) {
+        unsafe {
+            (**self.count).weak.set(self.weak_count() + 1)
+        }
+    }
+}
+
+impl<T> Rc<T> where T: Clone {
+    /// Returns a unique mutable reference to the owned value, cloning it into a fresh,
+    /// single-owner allocation first if there is more than one (strong) reference to it, or if
+    /// any `Weak` reference to it exists (since that `Weak` could be upgraded at any time).
+    pub fn make_mut(&mut self) -> &mut T {
+        if self.count() != 1 || self.weak_count() != 0 {
+            *self = Rc::new((**self).clone());
+        }
+
+        self.get_mut().unwrap()
+    }
+}
+
+/// A non-owning reference to a value managed by an `Rc`.
+///
+/// Unlike `Rc`, a `Weak` does not keep its pointee alive; it must be `upgrade`d to an `Rc` before
+/// the value can be accessed, which fails once the last strong reference has been dropped. This
+/// makes `Weak` suitable for breaking reference cycles.
+#[unsafe_no_drop_flag]
+pub struct Weak<T: ?Sized> {
+    /// The strong/weak reference counts, shared with the originating `Rc`
+    count: NonZero<*mut Counts>,
+    /// A pointer to the heap allocated data
+    data: NonZero<*mut T>,
 }
 
+impl<T: ?Sized> Weak<T> {
+    /// Returns the number of strong references to this value.
+    pub fn count(&self) -> usize {
+        unsafe {
+            (**self.count).strong.get()
+        }
+    }
+
+    /// Returns the number of `Weak` references to this value.
+    pub fn weak_count(&self) -> usize {
+        unsafe {
+            (**self.count).weak.get()
+        }
+    }
+
+    /// Attempts to upgrade this `Weak` pointer to a `Rc`, returning `None` if the value has
+    /// already been dropped.
+    pub fn upgrade(&self) -> Option<Rc<T>> {
+        if self.count() == 0 {
+            None
+        } else {
+            unsafe {
+                (**self.count).strong.set(self.count() + 1)
+            }
+
+            Some(Rc {
+                count: self.count,
+                data: self.data,
+            })
+        }
+    }
+
+    fn dec_weak(&self) {
+        unsafe {
+            (**self.count).weak.set(self.weak_count() - 1)
+        }
+    }
+
+    fn inc_weak(&self) {
+        unsafe {
+            (**self.count).weak.set(self.weak_count() + 1)
+        }
+    }
+}
+
+impl<T: ?Sized> Clone for Weak<T> {
+    fn clone(&self) -> Weak<T> {
+        self.inc_weak();
+
+        Weak {
+            count: self.count,
+            data: self.data,
+        }
+    }
+}
+
+impl<T: ?Sized> Drop for Weak<T> {
+    fn drop(&mut self) {
+        let ptr = *self.count;
+
+        if !ptr.is_null() && ptr as usize != mem::POST_DROP_USIZE {
+            unsafe {
+                self.dec_weak();
+
+                if self.count() == 0 && self.weak_count() == 0 {
+                    drop(Box::from_raw(*self.count));
+                }
+            }
+        }
+    }
+}
+
+impl<T> !Send for Weak<T> {}
+impl<T> !Sync for Weak<T> {}
+
 impl<T: ?Sized> Borrow<T> for Rc<T> {
     fn borrow(&self) -> &T {
         self
@@ -153,13 +430,37 @@ impl<'a> From<&'a str> for Rc<str> {
     }
 }
 
+impl<T> Rc<[T]> where T: Clone {
+    /// Attempts to build a `Rc<[T]>` from `slice`, returning `Err` instead of aborting if
+    /// allocation fails.
+    ///
+    /// NOTE: This requires allocating the `slice` first (`Vec::to_vec`), which may itself abort;
+    /// only the final `Rc` allocation performed by `try_from_vec` is fallible.
+    pub fn try_from_slice(slice: &[T]) -> Result<Rc<[T]>, AllocError> {
+        Rc::try_from_vec(slice.to_vec())
+    }
+}
+
+impl Rc<str> {
+    /// Attempts to build a `Rc<str>` from `string`, returning `Err` instead of aborting if
+    /// allocation fails.
+    ///
+    /// NOTE: This requires allocating `string` first (`String::from_str`), which may itself
+    /// abort; only the final `Rc` allocation performed by `try_from_string` is fallible.
+    pub fn try_from_str(string: &str) -> Result<Rc<str>, AllocError> {
+        Rc::try_from_string(String::from_str(string))
+    }
+}
+
 impl<T: ?Sized> From<Box<T>> for Rc<T> {
     /// NOTE: this involves a single, small heap allocation for the reference count. `boxed_value`
     /// will *not* be reallocated.
     fn from(boxed_value: Box<T>) -> Rc<T> {
         unsafe {
+            let counts = Counts { strong: Cell::new(1), weak: Cell::new(0) };
+
             Rc {
-                count: NonZero::new(boxed::into_raw(Box::new(Cell::new(1)))),
+                count: NonZero::new(boxed::into_raw(Box::new(counts))),
                 data: NonZero::new(boxed::into_raw(boxed_value)),
             }
         }
@@ -178,6 +479,21 @@ impl From<String> for Rc<str> {
     }
 }
 
+impl Rc<str> {
+    /// Attempts to build a `Rc<str>` from `string`, returning `Err` instead of aborting if
+    /// allocation fails.
+    ///
+    /// NOTE: This calls `shrink_to_fit` on `string` (on the underlying `Vec<u8>`), which may
+    /// itself abort; only the final `Rc` allocation performed by `try_from_vec` is fallible.
+    pub fn try_from_string(string: String) -> Result<Rc<str>, AllocError> {
+        // Create a `Rc<[u8]>` first, and then transmute that into a `Rc<str>`
+        match Rc::try_from_vec(string.into_bytes()) {
+            Ok(rc) => Ok(unsafe { mem::transmute(rc) }),
+            Err(e) => Err(e),
+        }
+    }
+}
+
 impl<T> From<Vec<T>> for Rc<[T]> {
     /// NOTE: This calls `shrink_to_fit` on `vec`, which may incur in a reallocation.
     fn from(vec: Vec<T>) -> Rc<[T]> {
@@ -185,6 +501,49 @@ impl<T> From<Vec<T>> for Rc<[T]> {
     }
 }
 
+impl<T> Rc<[T]> {
+    /// Attempts to build a `Rc<[T]>` from `vec`, returning `Err` instead of aborting if
+    /// allocation fails.
+    ///
+    /// NOTE: This calls `shrink_to_fit` on `vec`, which may itself abort; only the final `Rc`
+    /// allocation performed by `try_from_box` is fallible.
+    pub fn try_from_vec(vec: Vec<T>) -> Result<Rc<[T]>, AllocError> {
+        Rc::try_from_box(vec.into_boxed_slice())
+    }
+}
+
+impl<T> FromIterator<T> for Rc<[T]> {
+    /// NOTE: This collects into a `Vec<T>` first, then reuses `From<Vec<T>>` (honoring the same
+    /// `shrink_to_fit` note).
+    fn from_iter<I>(iter: I) -> Rc<[T]> where I: IntoIterator<Item=T> {
+        Rc::from(iter.into_iter().collect::<Vec<T>>())
+    }
+}
+
+impl FromIterator<char> for Rc<str> {
+    /// NOTE: This collects into a `String` first, then reuses `From<String>` (honoring the same
+    /// `shrink_to_fit` note).
+    fn from_iter<I>(iter: I) -> Rc<str> where I: IntoIterator<Item=char> {
+        Rc::from(iter.into_iter().collect::<String>())
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Rc<str> {
+    /// NOTE: This collects into a `String` first, then reuses `From<String>` (honoring the same
+    /// `shrink_to_fit` note).
+    fn from_iter<I>(iter: I) -> Rc<str> where I: IntoIterator<Item=&'a str> {
+        Rc::from(iter.into_iter().collect::<String>())
+    }
+}
+
+impl FromIterator<String> for Rc<str> {
+    /// NOTE: This collects into a `String` first, then reuses `From<String>` (honoring the same
+    /// `shrink_to_fit` note).
+    fn from_iter<I>(iter: I) -> Rc<str> where I: IntoIterator<Item=String> {
+        Rc::from(iter.into_iter().collect::<String>())
+    }
+}
+
 impl<T: ?Sized> Deref for Rc<T> {
     type Target = T;
 
@@ -204,8 +563,15 @@ impl<T: ?Sized> Drop for Rc<T> {
                 self.dec_count();
 
                 if self.count() == 0 {
-                    drop(Box::from_raw(*self.count));
+                    // Hold a temporary weak reference while the data is dropped, so that a
+                    // `Weak` dropped by the data's destructor can't free the count block.
+                    self.inc_weak();
                     drop(Box::from_raw(*self.data));
+                    self.dec_weak();
+
+                    if self.weak_count() == 0 {
+                        drop(Box::from_raw(*self.count));
+                    }
                 }
             }
         }
@@ -232,7 +598,7 @@ mod test {
     use rand::{Rng, XorShiftRng, self};
     use quickcheck::TestResult;
 
-    use Rc;
+    use {Arc, Rc, Weak};
 
     #[test]
     fn closure_borrow() {
@@ -312,6 +678,187 @@ mod test {
         copies.iter().all(|rcstr| &**rcstr == original)
     }
 
+    #[test]
+    fn try_new_succeeds_under_normal_conditions() {
+        let rc = Rc::try_new(42).unwrap();
+
+        assert_eq!(*rc, 42);
+    }
+
+    /// A zero-sized `T` must not spuriously fail to allocate.
+    #[test]
+    fn try_new_succeeds_for_a_zero_sized_type() {
+        let rc = Rc::try_new(()).unwrap();
+
+        assert_eq!(*rc, ());
+    }
+
+    #[test]
+    fn try_from_box_succeeds_under_normal_conditions() {
+        let boxed: Box<i32> = Box::new(42);
+        let rc = Rc::try_from_box(boxed).unwrap();
+
+        assert_eq!(*rc, 42);
+    }
+
+    #[test]
+    fn try_from_vec_slice_string_and_str() {
+        let from_vec = Rc::try_from_vec(vec![1, 2, 3]).unwrap();
+        assert_eq!(&*from_vec, &[1, 2, 3][..]);
+
+        let from_slice: Rc<[i32]> = Rc::try_from_slice(&[1, 2, 3]).unwrap();
+        assert_eq!(&*from_slice, &[1, 2, 3][..]);
+
+        let from_string = Rc::try_from_string("hello".to_string()).unwrap();
+        assert_eq!(&*from_string, "hello");
+
+        let from_str = Rc::try_from_str("hello").unwrap();
+        assert_eq!(&*from_str, "hello");
+    }
+
+    #[test]
+    fn get_mut_refuses_while_a_weak_ref_is_outstanding() {
+        let mut rc = Rc::new(1);
+        let weak = rc.downgrade();
+
+        assert!(rc.get_mut().is_none());
+
+        drop(weak);
+
+        assert!(rc.get_mut().is_some());
+    }
+
+    #[test]
+    fn make_mut_clones_away_instead_of_aliasing_a_weak_ref() {
+        let mut rc = Rc::new(1);
+        let weak = rc.downgrade();
+        let upgraded = weak.upgrade().unwrap();
+
+        *rc.make_mut() = 2;
+
+        // `make_mut` had to clone into a fresh allocation because a `Weak` existed, so the
+        // `Rc` obtained by upgrading that `Weak` earlier must be unaffected.
+        assert_eq!(*upgraded, 1);
+        assert_eq!(*rc, 2);
+    }
+
+    #[test]
+    fn try_unwrap_succeeds_when_unique() {
+        let rc = Rc::new(42);
+
+        match rc.try_unwrap() {
+            Ok(value) => assert_eq!(value, 42),
+            Err(_) => panic!("try_unwrap should have succeeded"),
+        }
+    }
+
+    #[test]
+    fn try_unwrap_fails_when_shared() {
+        let rc = Rc::new(42);
+        let clone = rc.clone();
+
+        match rc.try_unwrap() {
+            Ok(_) => panic!("try_unwrap should have failed"),
+            Err(rc) => assert_eq!(*rc, 42),
+        }
+
+        assert_eq!(*clone, 42);
+    }
+
+    #[test]
+    fn into_inner_roundtrip() {
+        let rc = Rc::new(42);
+        assert_eq!(rc.into_inner(), Some(42));
+
+        let rc = Rc::new(42);
+        let _clone = rc.clone();
+        assert_eq!(rc.into_inner(), None);
+    }
+
+    #[test]
+    fn from_iterator_impls() {
+        let rc_slice: Rc<[i32]> = vec![1, 2, 3].into_iter().collect();
+        assert_eq!(&*rc_slice, &[1, 2, 3][..]);
+
+        let rc_str: Rc<str> = "hello".chars().collect();
+        assert_eq!(&*rc_str, "hello");
+
+        let rc_str_from_strs: Rc<str> = vec!["foo", "bar"].into_iter().collect();
+        assert_eq!(&*rc_str_from_strs, "foobar");
+
+        let rc_str_from_strings: Rc<str> =
+            vec!["foo".to_string(), "bar".to_string()].into_iter().collect();
+        assert_eq!(&*rc_str_from_strings, "foobar");
+    }
+
+    #[test]
+    fn arc_refcounting() {
+        let arc = Arc::new(42);
+        assert_eq!(arc.count(), 1);
+
+        let clone = arc.clone();
+        assert_eq!(arc.count(), 2);
+        assert_eq!(*clone, 42);
+
+        drop(clone);
+        assert_eq!(arc.count(), 1);
+    }
+
+    /// `&[T]`/`Vec<T>` -> `Arc<[T]>`, with a non-`Copy` payload so a use-after-free or
+    /// double-free in the `Drop` path would actually be observable.
+    #[test]
+    fn arc_from_slice_and_vec_survive_clone_and_drop() {
+        let original = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+
+        let from_slice: Arc<[String]> = Arc::from(&original[..]);
+        let clone = from_slice.clone();
+        drop(from_slice);
+        assert_eq!(&*clone, &original[..]);
+        drop(clone);
+
+        let from_vec: Arc<[String]> = Arc::from(original.clone());
+        let clone = from_vec.clone();
+        drop(from_vec);
+        assert_eq!(&*clone, &original[..]);
+    }
+
+    /// `&str`/`String` -> `Arc<str>`, with a heap-allocated payload so a use-after-free or
+    /// double-free in the `Drop` path would actually be observable.
+    #[test]
+    fn arc_from_str_and_string_survive_clone_and_drop() {
+        let original = "Hello, world!".to_string();
+
+        let from_str: Arc<str> = Arc::from(&original[..]);
+        let clone = from_str.clone();
+        drop(from_str);
+        assert_eq!(&*clone, &*original);
+        drop(clone);
+
+        let from_string: Arc<str> = Arc::from(original.clone());
+        let clone = from_string.clone();
+        drop(from_string);
+        assert_eq!(&*clone, &*original);
+    }
+
+    #[test]
+    fn arc_eq_and_hash() {
+        use std::hash::{Hash, Hasher, SipHasher};
+
+        fn hash_of<T: Hash>(value: &T) -> u64 {
+            let mut hasher = SipHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let a = Arc::new("same".to_string());
+        let b = Arc::new("same".to_string());
+        let c = Arc::new("different".to_string());
+
+        assert!(a == b);
+        assert!(a != c);
+        assert_eq!(hash_of(&a), hash_of(&b));
+    }
+
     /// `Box<Fn(..) -> ..>` -> `Rc<Fn(..) -> ..>`
     #[test]
     fn rc_fn() {
@@ -321,6 +868,34 @@ mod test {
         assert_eq!(rc_fn(), 0);
     }
 
+    #[test]
+    fn weak_strong_and_weak_refcounting() {
+        let rc = Rc::new(42);
+        let weak: Weak<i32> = rc.downgrade();
+
+        assert_eq!(rc.count(), 1);
+        assert_eq!(rc.weak_count(), 1);
+        assert_eq!(weak.count(), 1);
+        assert_eq!(weak.weak_count(), 1);
+
+        let upgraded = weak.clone().upgrade().unwrap();
+        assert_eq!(*upgraded, 42);
+        assert_eq!(rc.count(), 2);
+
+        drop(upgraded);
+        assert_eq!(rc.count(), 1);
+    }
+
+    #[test]
+    fn weak_upgrade_fails_once_last_strong_ref_is_dropped() {
+        let rc = Rc::new(42);
+        let weak = rc.downgrade();
+
+        drop(rc);
+
+        assert!(weak.upgrade().is_none());
+    }
+
     #[should_panic]
     #[test]
     fn unwind() {
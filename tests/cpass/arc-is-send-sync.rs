@@ -0,0 +1,16 @@
+// Positive counterpart to `cfail/not-sync.rs`: unlike `Rc`, `Arc<T>` *is* `Send + Sync`
+// (for `T: Send + Sync`), so this is expected to compile cleanly.
+
+extern crate rc;
+
+use rc::Arc;
+
+fn is_send_and_sync<T: Send + Sync>(_: T) {}
+
+fn arc_is_send_and_sync<T: Send + Sync>(arc: Arc<T>) {
+    is_send_and_sync(arc);
+}
+
+fn main() {
+    arc_is_send_and_sync(Arc::new(0));
+}